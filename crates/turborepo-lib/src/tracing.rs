@@ -1,10 +1,16 @@
 use std::{
-    collections::HashMap, io::Stderr, marker::PhantomData, path::Path, sync::Mutex, time::Duration,
+    collections::HashMap,
+    io::{Stderr, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
 };
 
 use chrono::Local;
 use clap::Parser;
-use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use flate2::{write::GzEncoder, Compression};
+use opentelemetry::{propagation::TextMapPropagator, trace::TracerProvider as _, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{
     runtime,
@@ -17,16 +23,19 @@ use owo_colors::{
 };
 use serde::Serialize;
 use tracing::{field::Visit, metadata::LevelFilter, trace, Event, Level, Span, Subscriber};
-use tracing_appender::{non_blocking::NonBlocking, rolling::RollingFileAppender};
+use tracing_appender::{
+    non_blocking::NonBlocking,
+    rolling::{RollingFileAppender, Rotation},
+};
 use tracing_chrome::ChromeLayer;
 use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
 pub use tracing_subscriber::reload::Error;
 use tracing_subscriber::{
-    filter::Filtered,
+    filter::{Directive, Filtered},
     fmt::{
         self,
         format::{DefaultFields, Writer},
-        FmtContext, FormatEvent, FormatFields, MakeWriter,
+        FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter,
     },
     layer,
     prelude::*,
@@ -34,6 +43,7 @@ use tracing_subscriber::{
     reload::{self, Handle},
     EnvFilter, Layer, Registry,
 };
+use tracing_tracy::TracyLayer;
 use turborepo_ui::UI;
 
 // a lot of types to make sure we record the right relationships
@@ -54,7 +64,7 @@ impl<'a> MakeWriter<'a> for StdErrWrapper {
 /// The first generic parameter refers to the previous layer, which
 /// is in this case the default layer (`Registry`).
 type StdErrLog = fmt::Layer<Registry, DefaultFields, TurboFormatter, StdErrWrapper>;
-/// We filter this using an EnvFilter.
+/// We filter this using an EnvFilter, plus any per-module overrides.
 type StdErrLogFiltered = Filtered<StdErrLog, EnvFilter, Registry>;
 /// When the `StdErrLogFiltered` is applied to the `Registry`, we get a
 /// `StdErrLogLayered`, which forms the base for the next layer.
@@ -68,6 +78,7 @@ type DaemonReload = reload::Layer<Option<DaemonLog>, StdErrLogLayered>;
 /// We filter this using a custom filter that only logs events
 /// - with evel `TRACE` or higher for the `turborepo` target
 /// - with level `INFO` or higher for all other targets
+/// plus any per-module overrides.
 type DaemonLogFiltered = Filtered<DaemonReload, EnvFilter, StdErrLogLayered>;
 /// When the `DaemonLogFiltered` is applied to the `StdErrLogLayered`, we get a
 /// `DaemonLogLayered`, which forms the base for the next layer.
@@ -82,10 +93,64 @@ type ChromeReload = reload::Layer<Option<ChromeLog>, DaemonLogLayered>;
 /// `ChromeLogLayered`, which forms the base for the next layer.
 type ChromeLogLayered = layer::Layered<ChromeReload, DaemonLogLayered>;
 
-type OpenTelemetryLog = OpenTelemetryLayer<ChromeLogLayered, Tracer>;
-type OpenTelemetryReload = reload::Layer<Option<OpenTelemetryLog>, ChromeLogLayered>;
-type OpenTelemetryFiltered = Filtered<OpenTelemetryReload, EnvFilter, ChromeLogLayered>;
-type OpenTelemetryLayered = layer::Layered<OpenTelemetryReload, ChromeLogLayered>;
+/// A layer that streams spans to the Tracy profiler UI in real time. It is
+/// applied on top of the `ChromeLogLayered` layer, alongside chrome tracing.
+type TracyLog = TracyLayer;
+/// This layer can be reloaded. `None` means the layer is disabled.
+type TracyReload = reload::Layer<Option<TracyLog>, ChromeLogLayered>;
+/// When the `TracyReload` is applied to the `ChromeLogLayered`, we get a
+/// `TracyLogLayered`, which forms the base for the next layer.
+type TracyLogLayered = layer::Layered<TracyReload, ChromeLogLayered>;
+
+type OpenTelemetryLog = OpenTelemetryLayer<TracyLogLayered, Tracer>;
+type OpenTelemetryReload = reload::Layer<Option<OpenTelemetryLog>, TracyLogLayered>;
+type OpenTelemetryFiltered = Filtered<OpenTelemetryReload, EnvFilter, TracyLogLayered>;
+type OpenTelemetryLayered = layer::Layered<OpenTelemetryFiltered, TracyLogLayered>;
+
+/// A layer that exports events to syslog, for operators running `turbod` as
+/// a background service. It is applied on top of the `OpenTelemetryLayered`
+/// layer.
+type SyslogLog = SyslogLayer;
+/// This layer can be reloaded. `None` means the layer is disabled, which is
+/// the default so interactive invocations are unaffected.
+type SyslogReload = reload::Layer<Option<SyslogLog>, OpenTelemetryLayered>;
+/// We filter this using an EnvFilter, plus any per-module overrides, same as
+/// the other optional sinks: without a filter, the layer's interest in
+/// `TRACE` once enabled would raise the subscriber's global max level and
+/// flood syslog regardless of the configured verbosity.
+type SyslogFiltered = Filtered<SyslogReload, EnvFilter, OpenTelemetryLayered>;
+
+/// Parses `target=level` directives (e.g. `turborepo::daemon=trace`) from
+/// `log_targets`, merging in any directives from the `TURBO_LOG_TARGETS` env
+/// var (comma-separated). These are meant to be folded into an `EnvFilter`
+/// via `add_directive`, which resolves overlapping directives by specificity
+/// rather than by the order they were added, so a directive here can widen a
+/// module above the global verbosity just as easily as it can narrow it.
+fn build_log_target_directives(log_targets: &[String]) -> Vec<Directive> {
+    let from_env = std::env::var("TURBO_LOG_TARGETS").unwrap_or_default();
+    log_targets
+        .iter()
+        .map(String::as_str)
+        .chain(from_env.split(',').map(str::trim))
+        .filter(|directive| !directive.is_empty())
+        .filter_map(|directive| match directive.parse::<Directive>() {
+            Ok(directive) => Some(directive),
+            Err(e) => {
+                tracing::warn!("ignoring invalid log target directive {}: {}", directive, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Errors from enabling the opentelemetry export layer.
+#[derive(Debug, thiserror::Error)]
+pub enum TracingError {
+    #[error(transparent)]
+    Reload(#[from] Error),
+    #[error("failed to build opentelemetry exporter: {0}")]
+    Exporter(String),
+}
 
 pub struct TurboSubscriber {
     daemon_update: Handle<Option<DaemonLog>, StdErrLogLayered>,
@@ -97,9 +162,24 @@ pub struct TurboSubscriber {
     chrome_update: Handle<Option<ChromeLog>, DaemonLogLayered>,
     chrome_guard: Mutex<Option<tracing_chrome::FlushGuard>>,
 
-    opentelemetry_update: Handle<Option<OpenTelemetryLog>, ChromeLogLayered>,
+    tracy_update: Handle<Option<TracyLog>, ChromeLogLayered>,
+    /// The Tracy client only streams zones to the profiler UI while this
+    /// guard is held. We keep it here so that it doesn't get dropped.
+    tracy_guard: Mutex<Option<tracing_tracy::client::Client>>,
+
+    opentelemetry_update: Handle<Option<OpenTelemetryLog>, TracyLogLayered>,
     open_telemetry_guard: Mutex<Option<TracerProvider>>,
 
+    syslog_update: Handle<Option<SyslogLog>, OpenTelemetryLayered>,
+
+    /// The root span for this invocation of turbo, covering the whole
+    /// process rather than any one method call. A span only gets an otel
+    /// extension if the otel layer is already active when it's created, so
+    /// `enable_opentelemetry_tracing` replaces this with a fresh span (and
+    /// parents it to an inbound `traceparent`) once the layer is reloaded to
+    /// `Some`; until then it holds a plain, otel-less placeholder.
+    root_span: Mutex<Span>,
+
     #[cfg(feature = "pprof")]
     pprof_guard: pprof::ProfilerGuard<'static>,
     verbosity: usize,
@@ -117,13 +197,20 @@ impl TurboSubscriber {
     /// - If the verbosity argument (usually detemined by a flag) is provided,
     ///   it overrides the default global log level. This means it overrides the
     ///   `TURBO_LOG_VERBOSITY` global setting, but not per-module settings.
+    /// - `log_targets` (usually collected from a repeatable `--log-target`
+    ///   flag, plus anything in the `TURBO_LOG_TARGETS` env var) takes
+    ///   `target=level` directives, e.g. `turborepo::daemon=trace`, that
+    ///   override the verbosity for specific modules. These layer on top of,
+    ///   rather than replace, the global level above.
     ///
     /// `TurboSubscriber` has optional loggers that can be enabled later:
     /// - `set_daemon_logger` enables logging to a file, using the standard
     ///  formatter.
     /// - `enable_chrome_tracing` enables logging to a file, using the chrome
     ///  tracing formatter.
-    pub fn new_with_verbosity(verbosity: usize, ui: &UI) -> Self {
+    pub fn new_with_verbosity(verbosity: usize, ui: &UI, log_targets: &[String]) -> Self {
+        let target_directives = build_log_target_directives(log_targets);
+
         let env_filter = |level: LevelFilter| {
             let level_override = match verbosity {
                 0 => None,
@@ -139,16 +226,30 @@ impl TurboSubscriber {
                 .add_directive("hyper=warn".parse().unwrap())
                 .add_directive("h2=warn".parse().unwrap());
 
-            if let Some(max_level) = level_override {
+            let filter = if let Some(max_level) = level_override {
                 filter.add_directive(max_level.into())
             } else {
                 filter
-            }
+            };
+
+            // fold in the per-module overrides last, so `EnvFilter`'s
+            // specificity-based resolution lets a directive here widen a
+            // module above the global level, not just narrow it.
+            target_directives
+                .iter()
+                .cloned()
+                .fold(filter, |filter, directive| filter.add_directive(directive))
+        };
+
+        let formatter = if std::env::var("TURBO_LOG_FORMAT").as_deref() == Ok("json") {
+            TurboFormatter::new_json()
+        } else {
+            TurboFormatter::new_with_ansi(!ui.should_strip_ansi)
         };
 
         let stderr = fmt::layer()
             .with_writer(StdErrWrapper {})
-            .event_format(TurboFormatter::new_with_ansi(!ui.should_strip_ansi))
+            .event_format(formatter)
             .with_filter(env_filter(LevelFilter::WARN));
 
         // we set this layer to None to start with, effectively disabling it
@@ -157,44 +258,29 @@ impl TurboSubscriber {
 
         let (chrome, chrome_update) = reload::Layer::new(Option::<ChromeLog>::None);
 
+        let (tracy, tracy_update) = reload::Layer::new(Option::<TracyLog>::None);
+
         opentelemetry::global::set_text_map_propagator(
             opentelemetry_sdk::propagation::TraceContextPropagator::new(),
         );
 
-        let exporter = match opentelemetry_otlp::new_exporter()
-            .tonic()
-            .with_endpoint("http://localhost:4317")
-            .with_protocol(opentelemetry_otlp::Protocol::Grpc)
-            .with_timeout(Duration::from_secs(1))
-            .build_span_exporter()
-        {
-            Ok(ex) => ex,
-            Err(e) => {
-                tracing::error!("failed to enable opentelemetry tracing: {}", e);
-                panic!();
-            }
-        };
+        // we set this layer to None to start with, so opentelemetry tracing is off
+        // until `enable_opentelemetry_tracing` is called with a destination.
+        let (opentelemetry, opentelemetry_update) =
+            reload::Layer::new(Option::<OpenTelemetryLog>::None);
+        let opentelemetry: OpenTelemetryFiltered =
+            opentelemetry.with_filter(env_filter(LevelFilter::INFO));
 
-        let provider = TracerProvider::builder()
-            .with_simple_exporter(exporter)
-            .with_config(
-                opentelemetry_sdk::trace::Config::default()
-                    .with_resource(Resource::new(vec![KeyValue::new("service.name", "turbo")])),
-            )
-            .build();
-
-        let tracer = provider.tracer("turbo");
-
-        let opentelemetry = tracing_opentelemetry::layer().with_tracer(tracer);
-
-        let (_, opentelemetry_update) = reload::Layer::new(None);
-        let opentelemetry = opentelemetry.with_filter(env_filter(LevelFilter::INFO));
+        let (syslog, syslog_update) = reload::Layer::new(Option::<SyslogLog>::None);
+        let syslog: SyslogFiltered = syslog.with_filter(env_filter(LevelFilter::INFO));
 
         let registry = Registry::default()
             .with(stderr)
             .with(logrotate)
             .with(chrome)
-            .with(Some(opentelemetry));
+            .with(tracy)
+            .with(opentelemetry)
+            .with(syslog);
 
         #[cfg(feature = "pprof")]
         let pprof_guard = pprof::ProfilerGuardBuilder::default()
@@ -205,25 +291,66 @@ impl TurboSubscriber {
 
         registry.init();
 
+        // created after `registry.init()` so it's actually recorded by the
+        // subscriber we just installed, rather than a no-op span. The otel
+        // layer is still disabled at this point, so this span won't carry
+        // otel data until `enable_opentelemetry_tracing` replaces it.
+        let root_span = tracing::info_span!("turbo");
+
         Self {
             daemon_update,
             daemon_guard: Mutex::new(None),
             chrome_update,
             chrome_guard: Mutex::new(None),
+            tracy_update,
+            tracy_guard: Mutex::new(None),
             opentelemetry_update,
-            open_telemetry_guard: Mutex::new(Some(provider)),
+            open_telemetry_guard: Mutex::new(None),
+            syslog_update,
+            root_span: Mutex::new(root_span),
             #[cfg(feature = "pprof")]
             pprof_guard,
             verbosity,
         }
     }
 
-    /// Enables daemon logging with the specified rotation settings.
+    /// Returns turbo's root span for this invocation, so callers can enter
+    /// it for the lifetime of the process and have all other spans nest
+    /// under it.
+    pub fn root_span(&self) -> Span {
+        self.root_span.lock().expect("not poisoned").clone()
+    }
+
+    /// Enables daemon logging in `directory`, with the naming and retention
+    /// settings given by `config`.
     ///
-    /// Daemon logging uses the standard tracing formatter.
-    #[tracing::instrument(skip(self, appender))]
-    pub fn set_daemon_logger(&self, appender: RollingFileAppender) -> Result<(), Error> {
-        let (file_writer, guard) = tracing_appender::non_blocking(appender);
+    /// Daemon logging uses the standard tracing formatter. Rotated-out log
+    /// files are pruned and (optionally) compressed according to `config`,
+    /// so long-running daemons don't fill the disk with old logs.
+    #[tracing::instrument(skip(self))]
+    pub fn set_daemon_logger(
+        &self,
+        directory: impl AsRef<Path>,
+        config: DaemonLogConfig,
+    ) -> Result<(), Error> {
+        let directory = directory.as_ref().to_path_buf();
+
+        let appender = tracing_appender::rolling::Builder::new()
+            .rotation(config.rotation.to_tracing_rotation())
+            .filename_prefix(&config.file_name_prefix)
+            .filename_suffix(&config.file_name_suffix)
+            .build(&directory);
+
+        let appender = match appender {
+            Ok(appender) => appender,
+            Err(e) => {
+                tracing::error!("failed to create daemon log file: {}", e);
+                return Ok(());
+            }
+        };
+
+        let (file_writer, guard) =
+            tracing_appender::non_blocking(PruningAppender::new(appender, directory, config));
         trace!("created non-blocking file writer");
 
         let layer: DaemonLog = tracing_subscriber::fmt::layer()
@@ -262,15 +389,95 @@ impl TurboSubscriber {
         Ok(())
     }
 
-    /// Enables open telemetry tracing.
+    /// Enables the Tracy profiler, streaming turbo's spans as zones so they
+    /// can be viewed live in the Tracy profiler UI.
+    #[tracing::instrument(skip(self))]
+    pub fn enable_tracy(&self) -> Result<(), Error> {
+        let client = tracing_tracy::client::Client::start();
+
+        self.tracy_update.reload(Some(TracyLayer::new()))?;
+        self.tracy_guard
+            .lock()
+            .expect("not poisoned")
+            .replace(client);
+
+        Ok(())
+    }
+
+    /// Enables a syslog export layer, for operators running `turbod` as a
+    /// background service who want logs in journald/rsyslog rather than
+    /// scraping files. Disabled by default, so interactive invocations are
+    /// unaffected.
+    #[tracing::instrument(skip(self))]
+    pub fn enable_syslog(&self, facility: syslog::Facility, ident: String) -> Result<(), Error> {
+        let layer = match SyslogLayer::new(facility, ident) {
+            Ok(layer) => layer,
+            Err(e) => {
+                tracing::error!("failed to enable syslog: {}", e);
+                return Ok(());
+            }
+        };
+
+        self.syslog_update.reload(Some(layer))?;
+
+        Ok(())
+    }
+
+    /// Enables open telemetry tracing, exporting spans over grpc to
+    /// `config.destination`.
+    ///
+    /// Once the otel layer is active, this replaces turbo's root span (see
+    /// [`Self::root_span`]) with a fresh one that the layer can actually
+    /// attach otel data to. If `config.traceparent` is set, it is parsed as a
+    /// W3C trace-context header and used as that span's parent, so that a
+    /// turbo invoked from another service has its spans stitched into the
+    /// caller's trace.
     #[tracing::instrument(skip(self, config))]
-    pub fn enable_opentelemetry_tracing(&self, config: &OtelConfig) -> Result<(), Error> {
-        // self.opentelemetry_update.modify(|l| *l = Some(layer))?;
-        // self.open_telemetry_guard
-        //     .lock()
-        //     .expect("not poisoned")
-        //     .replace(provider);
-        // tracing::debug!("opentelemetry tracing enabled");
+    pub fn enable_opentelemetry_tracing(&self, config: &OtelConfig) -> Result<(), TracingError> {
+        let Some((destination, traceparent)) = config.flatten() else {
+            // no destination configured, so opentelemetry tracing stays disabled
+            return Ok(());
+        };
+
+        let exporter = opentelemetry_otlp::new_exporter()
+            .tonic()
+            .with_endpoint(destination)
+            .with_protocol(opentelemetry_otlp::Protocol::Grpc)
+            .with_timeout(Duration::from_secs(1))
+            .build_span_exporter()
+            .map_err(|e| TracingError::Exporter(e.to_string()))?;
+
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter)
+            .with_config(
+                opentelemetry_sdk::trace::Config::default()
+                    .with_resource(Resource::new(vec![KeyValue::new("service.name", "turbo")])),
+            )
+            .build();
+
+        let tracer = provider.tracer("turbo");
+        let layer = tracing_opentelemetry::layer().with_tracer(tracer);
+
+        self.opentelemetry_update.reload(Some(layer))?;
+        self.open_telemetry_guard
+            .lock()
+            .expect("not poisoned")
+            .replace(provider);
+
+        // the otel layer is active now, so a span created from this point on
+        // gets an otel extension; the old root span, created while the layer
+        // was still `None`, never could and can't retroactively gain one.
+        let root_span = tracing::info_span!("turbo");
+        if let Some(traceparent) = traceparent {
+            let mut carrier = HashMap::new();
+            carrier.insert("traceparent".to_string(), traceparent.to_owned());
+            let parent_context =
+                opentelemetry_sdk::propagation::TraceContextPropagator::new().extract(&carrier);
+            root_span.set_parent(parent_context);
+        }
+        *self.root_span.lock().expect("not poisoned") = root_span;
+
+        tracing::debug!("opentelemetry tracing enabled");
 
         Ok(())
     }
@@ -281,8 +488,6 @@ impl Drop for TurboSubscriber {
         // drop the guard so that the non-blocking file writer stops
         #[cfg(feature = "pprof")]
         if let Ok(report) = self.pprof_guard.report().build() {
-            use std::io::Write; // only import trait if we need it
-
             use prost::Message;
 
             let mut file = std::fs::File::create("pprof.pb").unwrap();
@@ -310,6 +515,241 @@ impl Drop for TurboSubscriber {
     }
 }
 
+/// How often the daemon log file rotates.
+///
+/// Mirrors `tracing_appender::rolling::Rotation`, but we keep our own copy
+/// so `PruningAppender` can key pruning/compression off the same period
+/// without depending on that type's private internals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonLogRotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+impl DaemonLogRotation {
+    fn to_tracing_rotation(self) -> Rotation {
+        match self {
+            DaemonLogRotation::Hourly => Rotation::HOURLY,
+            DaemonLogRotation::Daily => Rotation::DAILY,
+            DaemonLogRotation::Never => Rotation::NEVER,
+        }
+    }
+
+    /// A string that's constant within one rotation period and changes when
+    /// the log rotates, used both as the date component of the file name and
+    /// to detect that a rotation has happened.
+    fn period_key(self, now: chrono::DateTime<chrono::Utc>) -> String {
+        match self {
+            DaemonLogRotation::Hourly => now.format("%Y-%m-%d-%H").to_string(),
+            DaemonLogRotation::Daily => now.format("%Y-%m-%d").to_string(),
+            DaemonLogRotation::Never => "current".to_string(),
+        }
+    }
+}
+
+/// Configuration for the daemon's rotating log files: naming, retention, and
+/// compression.
+#[derive(Debug, Clone)]
+pub struct DaemonLogConfig {
+    /// Prefix for each log file's name, e.g. `turbod` produces
+    /// `turbod.2024-01-01.log`.
+    pub file_name_prefix: String,
+    /// Suffix (extension, without the leading dot) for each log file's name.
+    pub file_name_suffix: String,
+    /// How often to rotate to a new file.
+    pub rotation: DaemonLogRotation,
+    /// The maximum number of rotated-out files to keep. Files beyond this
+    /// count, oldest first, are deleted. `None` disables pruning.
+    pub max_files: Option<usize>,
+    /// Whether to gzip-compress rotated-out files that aren't the current
+    /// log file.
+    pub compress: bool,
+}
+
+impl Default for DaemonLogConfig {
+    fn default() -> Self {
+        Self {
+            file_name_prefix: "turbod".to_string(),
+            file_name_suffix: "log".to_string(),
+            rotation: DaemonLogRotation::Daily,
+            max_files: Some(7),
+            compress: false,
+        }
+    }
+}
+
+/// Wraps a `RollingFileAppender`, pruning old daemon log files and
+/// compressing rotated-out ones each time the log rotates into a new period.
+struct PruningAppender {
+    inner: RollingFileAppender,
+    directory: PathBuf,
+    config: DaemonLogConfig,
+    current_period: Mutex<String>,
+}
+
+impl PruningAppender {
+    fn new(inner: RollingFileAppender, directory: PathBuf, config: DaemonLogConfig) -> Self {
+        let current_period = Mutex::new(config.rotation.period_key(chrono::Utc::now()));
+        Self {
+            inner,
+            directory,
+            config,
+            current_period,
+        }
+    }
+
+    fn current_file_name(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.config.file_name_prefix,
+            self.config.rotation.period_key(chrono::Utc::now()),
+            self.config.file_name_suffix
+        )
+    }
+}
+
+impl Write for PruningAppender {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let period = self.config.rotation.period_key(chrono::Utc::now());
+        let mut current_period = self.current_period.lock().expect("not poisoned");
+        if *current_period != period {
+            *current_period = period;
+            prune_and_compress_daemon_logs(
+                &self.directory,
+                &self.config,
+                &self.current_file_name(),
+            );
+        }
+        drop(current_period);
+
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Enumerates the daemon log files in `directory` matching `config`'s naming
+/// scheme, deletes the oldest ones beyond `config.max_files`, and (if
+/// `config.compress` is set) gzip-compresses any rotated-out file that isn't
+/// already compressed. `current_file_name` is excluded from this set
+/// entirely, so the actively-written log can never be pruned or compressed
+/// out from under the writer, even with a small or zero `max_files`.
+fn prune_and_compress_daemon_logs(
+    directory: &Path,
+    config: &DaemonLogConfig,
+    current_file_name: &str,
+) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+
+    let prefix = format!("{}.", config.file_name_prefix);
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name != current_file_name)
+        })
+        .collect();
+    // the date is embedded in the file name, so lexicographic order is also
+    // chronological order
+    files.sort();
+
+    if let Some(max_files) = config.max_files {
+        let stale = files.len().saturating_sub(max_files);
+        for path in files.drain(..stale) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("failed to prune daemon log {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    if config.compress {
+        for path in &files {
+            let is_already_compressed = path.extension().is_some_and(|ext| ext == "gz");
+            if is_already_compressed {
+                continue;
+            }
+            if let Err(e) = compress_daemon_log(path) {
+                tracing::warn!("failed to compress daemon log {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+fn compress_daemon_log(path: &Path) -> std::io::Result<()> {
+    let contents = std::fs::read(path)?;
+
+    let mut gz_name = path
+        .file_name()
+        .expect("path has a file name")
+        .to_os_string();
+    gz_name.push(".gz");
+    let gz_path = path.with_file_name(gz_name);
+
+    let gz_file = std::fs::File::create(&gz_path)?;
+    let mut encoder = GzEncoder::new(gz_file, Compression::default());
+    encoder.write_all(&contents)?;
+    encoder.finish()?;
+
+    std::fs::remove_file(path)
+}
+
+/// Exports events to syslog, rendering the same message-only line body that
+/// `TurboFormatter` uses and mapping tracing levels to syslog severities:
+/// `ERROR`→err, `WARN`→warning, `INFO`→info, `DEBUG`/`TRACE`→debug.
+struct SyslogLayer {
+    logger: Mutex<syslog::Logger<syslog::LoggerBackend, String, syslog::Formatter3164>>,
+}
+
+impl SyslogLayer {
+    fn new(facility: syslog::Facility, ident: String) -> Result<Self, syslog::Error> {
+        let formatter = syslog::Formatter3164 {
+            facility,
+            hostname: None,
+            process: ident,
+            pid: std::process::id() as i32,
+        };
+
+        Ok(Self {
+            logger: Mutex::new(syslog::unix(formatter)?),
+        })
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for SyslogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: layer::Context<'_, S>) {
+        let mut message = String::new();
+        let mut visitor = MessageVisitor::<Default, Default> {
+            colorize: false,
+            writer: Writer::new(&mut message),
+            _fg: PhantomData,
+            _bg: PhantomData,
+        };
+        event.record(&mut visitor);
+
+        let mut logger = self.logger.lock().expect("not poisoned");
+        let result = match *event.metadata().level() {
+            Level::ERROR => logger.err(message),
+            Level::WARN => logger.warning(message),
+            Level::INFO => logger.info(message),
+            Level::DEBUG | Level::TRACE => logger.debug(message),
+        };
+
+        if let Err(e) = result {
+            eprintln!("failed to write to syslog: {}", e);
+        }
+    }
+}
+
 #[derive(Serialize, Parser, PartialEq, Clone, Debug)]
 pub struct OtelConfig {
     /// If turbo is being called by another service, setting the trace parent
@@ -325,7 +765,6 @@ pub struct OtelConfig {
 
 impl OtelConfig {
     fn flatten(&self) -> Option<(&String, Option<&String>)> {
-        println!("using {:?} {:?}", self.destination, self.traceparent);
         self.destination
             .as_ref()
             .map(|destination| (destination, self.traceparent.as_ref()))
@@ -343,13 +782,29 @@ impl OtelConfig {
 /// This formatter does not print any information about spans, and does
 /// not print any event metadata other than the message set when you
 /// call `debug!(...)` or `info!(...)` etc.
+///
+/// If `json` is set, this instead emits one NDJSON object per line,
+/// containing the timestamp, level, target, message, every recorded
+/// event field, and the current span stack, for machine ingestion
+/// (e.g. by CI systems or log shippers). Set via `TURBO_LOG_FORMAT=json`.
 pub struct TurboFormatter {
     is_ansi: bool,
+    json: bool,
 }
 
 impl TurboFormatter {
     pub fn new_with_ansi(is_ansi: bool) -> Self {
-        Self { is_ansi }
+        Self {
+            is_ansi,
+            json: false,
+        }
+    }
+
+    pub fn new_json() -> Self {
+        Self {
+            is_ansi: false,
+            json: true,
+        }
     }
 }
 
@@ -360,10 +815,14 @@ where
 {
     fn format_event(
         &self,
-        _ctx: &FmtContext<'_, S, N>,
+        ctx: &FmtContext<'_, S, N>,
         mut writer: Writer<'_>,
         event: &Event<'_>,
     ) -> std::fmt::Result {
+        if self.json {
+            return format_event_json(ctx, writer, event);
+        }
+
         let level = event.metadata().level();
         let target = event.metadata().target();
 
@@ -397,6 +856,71 @@ where
     }
 }
 
+/// Renders an event as a single line of NDJSON, including its fields and
+/// the current span stack, for machine consumption.
+fn format_event_json<S, N>(
+    ctx: &FmtContext<'_, S, N>,
+    mut writer: Writer<'_>,
+    event: &Event<'_>,
+) -> std::fmt::Result
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    let metadata = event.metadata();
+
+    let mut visitor = JsonVisitor::default();
+    event.record(&mut visitor);
+    let message = visitor
+        .fields
+        .remove("message")
+        .unwrap_or(serde_json::Value::Null);
+
+    let spans = ctx
+        .event_scope()
+        .map(|scope| {
+            scope
+                .from_root()
+                .map(|span| {
+                    let fields = span
+                        .extensions()
+                        .get::<FormattedFields<N>>()
+                        .map(|fields| fields.fields.clone())
+                        .unwrap_or_default();
+                    serde_json::json!({ "name": span.name(), "fields": fields })
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let line = serde_json::json!({
+        "timestamp": Local::now().format("%Y-%m-%dT%H:%M:%S.%3f%z").to_string(),
+        "level": metadata.level().as_str(),
+        "target": metadata.target(),
+        "message": message,
+        "fields": visitor.fields,
+        "spans": spans,
+    });
+
+    writeln!(writer, "{}", line)
+}
+
+/// A visitor that collects every recorded event field into a JSON object,
+/// for use by the NDJSON output mode.
+#[derive(Default)]
+struct JsonVisitor {
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.fields.insert(
+            field.name().to_string(),
+            serde_json::Value::String(format!("{:?}", value)),
+        );
+    }
+}
+
 /// A visitor that writes the message field of an event to the given writer.
 ///
 /// The FG and BG type parameters are the foreground and background colors
@@ -449,3 +973,197 @@ fn write_message<FG: Color, BG: Color>(
     event.record(&mut visitor);
     writeln!(writer)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct VecWriter(Arc<StdMutex<Vec<u8>>>);
+
+    impl Write for VecWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().expect("not poisoned").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for VecWriter {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn json_format_collects_message_and_fields() {
+        let buffer = VecWriter::default();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(TurboFormatter::new_json())
+            .with_writer(buffer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(answer = 42, "hello from the test");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().expect("not poisoned").clone())
+            .expect("valid utf8");
+        let line: serde_json::Value = serde_json::from_str(output.trim()).expect("valid json");
+
+        assert_eq!(line["message"], "hello from the test");
+        assert_eq!(line["fields"]["answer"], "42");
+        assert_eq!(line["level"], "INFO");
+    }
+
+    #[test]
+    fn json_format_nests_the_current_span_stack() {
+        let buffer = VecWriter::default();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(TurboFormatter::new_json())
+            .with_writer(buffer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("outer");
+            let _guard = span.enter();
+            tracing::warn!("nested event");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().expect("not poisoned").clone())
+            .expect("valid utf8");
+        let line: serde_json::Value = serde_json::from_str(output.trim()).expect("valid json");
+
+        assert_eq!(line["spans"][0]["name"], "outer");
+    }
+
+    fn write_daemon_log(dir: &Path, name: &str) {
+        std::fs::write(dir.join(name), "log line\n").expect("can write log file");
+    }
+
+    #[test]
+    fn prune_deletes_oldest_files_beyond_max_files_but_keeps_current() {
+        let dir = tempfile::tempdir().expect("can create tempdir");
+        let config = DaemonLogConfig {
+            max_files: Some(1),
+            compress: false,
+            ..Default::default()
+        };
+
+        for day in ["2024-01-01", "2024-01-02", "2024-01-03"] {
+            write_daemon_log(dir.path(), &format!("turbod.{day}.log"));
+        }
+        // the current file is the newest by name and doesn't count towards
+        // `max_files`, so one rotated-out file is kept in addition to it
+        let current = "turbod.2024-01-04.log";
+        write_daemon_log(dir.path(), current);
+
+        prune_and_compress_daemon_logs(dir.path(), &config, current);
+
+        let remaining: std::collections::BTreeSet<_> = std::fs::read_dir(dir.path())
+            .expect("can read dir")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            remaining,
+            ["turbod.2024-01-03.log", current]
+                .into_iter()
+                .map(String::from)
+                .collect()
+        );
+    }
+
+    #[test]
+    fn prune_with_max_files_zero_still_keeps_current_file() {
+        let dir = tempfile::tempdir().expect("can create tempdir");
+        let config = DaemonLogConfig {
+            max_files: Some(0),
+            compress: false,
+            ..Default::default()
+        };
+
+        let current = "turbod.2024-01-01.log";
+        write_daemon_log(dir.path(), current);
+
+        prune_and_compress_daemon_logs(dir.path(), &config, current);
+
+        let remaining: Vec<_> = std::fs::read_dir(dir.path())
+            .expect("can read dir")
+            .filter_map(|entry| entry.ok())
+            .collect();
+        assert_eq!(remaining.len(), 1);
+    }
+
+    #[test]
+    fn compress_skips_current_file_and_already_compressed_files() {
+        let dir = tempfile::tempdir().expect("can create tempdir");
+        let config = DaemonLogConfig {
+            max_files: None,
+            compress: true,
+            ..Default::default()
+        };
+
+        let current = "turbod.2024-01-03.log";
+        write_daemon_log(dir.path(), "turbod.2024-01-01.log");
+        write_daemon_log(dir.path(), "turbod.2024-01-02.log.gz");
+        write_daemon_log(dir.path(), current);
+
+        prune_and_compress_daemon_logs(dir.path(), &config, current);
+
+        let remaining: std::collections::BTreeSet<_> = std::fs::read_dir(dir.path())
+            .expect("can read dir")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            remaining,
+            [
+                "turbod.2024-01-01.log.gz",
+                "turbod.2024-01-02.log.gz",
+                current,
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect()
+        );
+    }
+
+    #[test]
+    fn build_log_target_directives_parses_target_level_pairs() {
+        let directives = build_log_target_directives(&[
+            "turborepo::daemon=trace".to_string(),
+            "turborepo::run=debug".to_string(),
+        ]);
+
+        let rendered: Vec<_> = directives.iter().map(Directive::to_string).collect();
+        assert_eq!(rendered, ["turborepo::daemon=trace", "turborepo::run=debug"]);
+    }
+
+    #[test]
+    fn build_log_target_directives_ignores_malformed_directives() {
+        let directives = build_log_target_directives(&[
+            "turborepo::daemon=not-a-level".to_string(),
+            "turborepo::run=info".to_string(),
+        ]);
+
+        let rendered: Vec<_> = directives.iter().map(Directive::to_string).collect();
+        assert_eq!(rendered, ["turborepo::run=info"]);
+    }
+
+    #[test]
+    fn build_log_target_directives_ignores_blank_entries() {
+        let directives =
+            build_log_target_directives(&["".to_string(), "turborepo=warn".to_string()]);
+
+        let rendered: Vec<_> = directives.iter().map(Directive::to_string).collect();
+        assert_eq!(rendered, ["turborepo=warn"]);
+    }
+}